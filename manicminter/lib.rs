@@ -7,6 +7,7 @@ mod manicminter {
         call::{build_call, ExecutionInput, Selector},
         DefaultEnvironment,
     };
+    use ink::storage::Mapping;
 
     /// Defines the storage of your contract.
     /// Add new fields to the below struct in order
@@ -15,8 +16,27 @@ mod manicminter {
     pub struct Manicminter {
         /// Stores a single `bool` value on the storage.
         owner: AccountId,
+        pending_owner: Option<AccountId>,
         token_contract: AccountId,
         price: Balance,
+        max_supply: Option<Balance>,
+        mint_selector: [u8; 4],
+        gas_limit: u64,
+        account_prices: Mapping<AccountId, Balance>,
+    }
+
+    #[ink(event)]
+    pub struct Minted {
+        #[ink(topic)]
+        minter: AccountId,
+        amount: Balance,
+        paid: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PriceChanged {
+        old: Balance,
+        new: Balance,
     }
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -24,13 +44,21 @@ mod manicminter {
     pub enum Error {
         BadMintValue,
         ContractNotSet,
+        InsufficientBalance,
+        MaxSupplyExceeded,
         NotOwner,
+        NotPendingOwner,
         Overflow,
+        SetCodeFailed,
         TransactionFailed,
     }
 
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// Default cross-contract call weight budget, matching the value this
+    /// contract used before the gas limit became configurable.
+    const DEFAULT_GAS_LIMIT: u64 = 5000000000;
+
     #[ink::trait_definition]
     pub trait Minting {
         #[ink(message, payable)]
@@ -49,10 +77,150 @@ mod manicminter {
         pub fn new(contract_account: AccountId) -> Self {
             Self {
                 owner: Self::env().caller(),
+                pending_owner: None,
                 token_contract: contract_account,
                 price: 0,
+                max_supply: None,
+                mint_selector: ink::selector_bytes!("PSP22Mintable::mint"),
+                gas_limit: DEFAULT_GAS_LIMIT,
+                account_prices: Mapping::default(),
             }
         }
+
+        /// Sets a per-account price that overrides the default `price` for `account`,
+        /// enabling presale or allowlist tiers.
+        #[ink(message)]
+        pub fn set_account_price(&mut self, account: AccountId, price: Balance) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.account_prices.insert(account, &price);
+            Ok(())
+        }
+
+        /// Returns the price `account` would pay, falling back to the default
+        /// `price` when no account-specific price has been set.
+        #[ink(message)]
+        pub fn get_price_for(&self, account: AccountId) -> Balance {
+            self.account_prices.get(account).unwrap_or(self.price)
+        }
+
+        /// Sets the selector used to call the token contract's mint entrypoint,
+        /// letting this minter drive `PSP22Mintable`, a custom `mint` function,
+        /// or any other ABI that exposes a `(AccountId, Balance) -> Result<()>` call.
+        #[ink(message)]
+        pub fn set_mint_selector(&mut self, selector: [u8; 4]) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.mint_selector = selector;
+            Ok(())
+        }
+
+        /// Returns the selector currently used to call the token contract's mint entrypoint.
+        #[ink(message)]
+        pub fn get_mint_selector(&self) -> [u8; 4] {
+            self.mint_selector
+        }
+
+        /// Sets the weight budget used for cross-contract calls into the token
+        /// contract, so chains with different weight budgets can be supported.
+        #[ink(message)]
+        pub fn set_gas_limit(&mut self, gas_limit: u64) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.gas_limit = gas_limit;
+            Ok(())
+        }
+
+        /// Returns the configured cross-contract call gas limit.
+        #[ink(message)]
+        pub fn get_gas_limit(&self) -> u64 {
+            self.gas_limit
+        }
+
+        /// Replaces this contract's code with the code at `code_hash`, preserving
+        /// its account id and storage.
+        ///
+        /// The new code must keep the `Manicminter` storage struct's field order
+        /// unchanged; ink!'s storage layout is positional, so reordering, removing,
+        /// or retyping a field corrupts every instance upgraded through this path.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| Error::SetCodeFailed)
+        }
+
+        /// Sets the hard cap on total token supply that `manic_mint` will allow,
+        /// or lifts it entirely when `cap` is `None`.
+        #[ink(message)]
+        pub fn set_max_supply(&mut self, cap: Option<Balance>) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.max_supply = cap;
+            Ok(())
+        }
+
+        /// Returns the configured supply cap, if any.
+        #[ink(message)]
+        pub fn get_max_supply(&self) -> Option<Balance> {
+            self.max_supply
+        }
+
+        /// Proposes `new_owner` as the successor to the current owner.
+        ///
+        /// The transfer only completes once `new_owner` calls [`Self::accept_ownership`],
+        /// guarding against locking the contract out by proposing an unreachable address.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        /// Completes a pending ownership transfer. Must be called by the proposed owner.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            ensure!(self.pending_owner == Some(caller), Error::NotPendingOwner);
+            self.owner = caller;
+            self.pending_owner = None;
+            Ok(())
+        }
+
+        /// Permanently removes the owner, leaving `set_price` and other
+        /// owner-gated messages unreachable.
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            self.owner = zero_address();
+            self.pending_owner = None;
+            Ok(())
+        }
+
+        /// Transfers `amount` of the contract's balance to the owner.
+        ///
+        /// Lets the owner sweep proceeds collected from `manic_mint` out of the
+        /// contract instead of leaving them trapped in its account.
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<()> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            ensure!(
+                self.env().balance() >= amount,
+                Error::InsufficientBalance
+            );
+
+            self.env()
+                .transfer(self.owner, amount)
+                .map_err(|_| Error::InsufficientBalance)
+        }
+
+        /// Emits a `Minted` event. Split out from `manic_mint` so the event
+        /// payload can be unit tested without routing through a cross-contract
+        /// mint call that off-chain tests can't satisfy.
+        fn emit_minted(&self, minter: AccountId, amount: Balance, paid: Balance) {
+            self.env().emit_event(Minted {
+                minter,
+                amount,
+                paid,
+            });
+        }
     }
 
     impl Minting for Manicminter {
@@ -61,21 +229,53 @@ mod manicminter {
             let caller = self.env().caller();
             ensure!(self.token_contract != zero_address(), Error::ContractNotSet);
 
-            match self.price.checked_mul(amount) {
+            let transferred_value = self.env().transferred_value();
+            let price = self.account_prices.get(caller).unwrap_or(self.price);
+            let value = match price.checked_mul(amount) {
                 Some(value) => {
-                    let transferred_value = self.env().transferred_value();
                     ensure!(transferred_value >= value, Error::TransactionFailed);
+                    value
                 }
                 None => {
                     return Err(Error::Overflow);
                 }
+            };
+
+            if let Some(cap) = self.max_supply {
+                let current_supply = build_call::<DefaultEnvironment>()
+                    .call(self.token_contract)
+                    .gas_limit(self.gas_limit)
+                    .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP22::total_supply"
+                    ))))
+                    .returns::<Balance>()
+                    .try_invoke()
+                    .map_err(|_| Error::TransactionFailed)?
+                    .map_err(|_| Error::TransactionFailed)?;
+
+                let requested = current_supply.checked_add(amount).ok_or(Error::Overflow)?;
+                if requested > cap {
+                    // Returning `Err` does not revert the transferred value already
+                    // credited to this contract, so hand it all back before rejecting
+                    // a mint we now know cannot happen.
+                    self.env()
+                        .transfer(caller, transferred_value)
+                        .map_err(|_| Error::TransactionFailed)?;
+                    return Err(Error::MaxSupplyExceeded);
+                }
+            }
+
+            if transferred_value > value {
+                self.env()
+                    .transfer(caller, transferred_value - value)
+                    .map_err(|_| Error::TransactionFailed)?;
             }
 
             let mint_result = build_call::<DefaultEnvironment>()
                 .call(self.token_contract)
-                .gas_limit(5000000000)
+                .gas_limit(self.gas_limit)
                 .exec_input(
-                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22Mintable::mint")))
+                    ExecutionInput::new(Selector::new(self.mint_selector))
                         .push_arg(caller)
                         .push_arg(amount),
                 )
@@ -83,7 +283,10 @@ mod manicminter {
                 .try_invoke();
 
             match mint_result {
-                Ok(Ok(_)) => Ok(()),
+                Ok(Ok(_)) => {
+                    self.emit_minted(caller, amount, value);
+                    Ok(())
+                }
                 _ => Err(Error::TransactionFailed),
             }
         }
@@ -91,7 +294,12 @@ mod manicminter {
         #[ink(message)]
         fn set_price(&mut self, new_price: Balance) -> Result<()> {
             ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            let old_price = self.price;
             self.price = new_price;
+            self.env().emit_event(PriceChanged {
+                old: old_price,
+                new: new_price,
+            });
             Ok(())
         }
 
@@ -112,21 +320,256 @@ mod manicminter {
     mod tests {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
+        use ink::env::test;
+
+        type Env = ink::env::DefaultEnvironment;
+        type Event = <Manicminter as ::ink::reflect::ContractEventBase>::Type;
+
+        /// `manic_mint` calls into `token_contract`, so its tests need a
+        /// non-zero address to get past the `ContractNotSet` guard. The
+        /// off-chain environment cannot invoke other contracts, so the
+        /// cross-contract mint call panics here; the `manic_mint` tests
+        /// instead verify the payment validation and refund logic that run
+        /// before that call is attempted.
+        fn token_contract_account() -> AccountId {
+            AccountId::from([0x1; 32])
+        }
 
-        /// We test if the default constructor does its job.
+        /// We test that `set_price` emits a `PriceChanged` event carrying the
+        /// old and new price.
         #[ink::test]
-        fn default_works() {
-            let manicminter = Manicminter::default();
-            assert_eq!(manicminter.get(), false);
+        fn set_price_emits_price_changed() {
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            manicminter.set_price(10).unwrap();
+            manicminter.set_price(25).unwrap();
+
+            let emitted_events = test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2);
+            let event = match <Event as scale::Decode>::decode(&mut &emitted_events[1].data[..])
+                .expect("encountered invalid contract event data buffer")
+            {
+                Event::PriceChanged(event) => event,
+                _ => panic!("expected a PriceChanged event"),
+            };
+            assert_eq!(event.old, 10);
+            assert_eq!(event.new, 25);
+        }
+
+        /// We test that `emit_minted`, which `manic_mint` calls after a
+        /// successful mint, emits a `Minted` event with the minter, amount
+        /// and amount actually paid.
+        #[ink::test]
+        fn emit_minted_emits_minted_event() {
+            let accounts = test::default_accounts::<Env>();
+            let manicminter = Manicminter::new(token_contract_account());
+
+            manicminter.emit_minted(accounts.bob, 3, 30);
+
+            let emitted_events = test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 1);
+            let event = match <Event as scale::Decode>::decode(&mut &emitted_events[0].data[..])
+                .expect("encountered invalid contract event data buffer")
+            {
+                Event::Minted(event) => event,
+                _ => panic!("expected a Minted event"),
+            };
+            assert_eq!(event.minter, accounts.bob);
+            assert_eq!(event.amount, 3);
+            assert_eq!(event.paid, 30);
+        }
+
+        /// We test that underpaying is rejected before any funds move.
+        #[ink::test]
+        fn manic_mint_rejects_underpayment() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+            manicminter.set_price(10).unwrap();
+
+            test::set_caller::<Env>(accounts.bob);
+            test::set_value_transferred::<Env>(5);
+
+            assert_eq!(manicminter.manic_mint(1), Err(Error::TransactionFailed));
+        }
+
+        /// We test that overpaying refunds the difference to the caller.
+        #[ink::test]
+        fn manic_mint_refunds_overpayment() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+            manicminter.set_price(10).unwrap();
+
+            test::set_caller::<Env>(accounts.bob);
+            test::set_value_transferred::<Env>(15);
+            let balance_before = test::get_account_balance::<Env>(accounts.bob).unwrap();
+
+            // The refund happens before the cross-contract mint call, which panics off-chain.
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                manicminter.manic_mint(1)
+            }));
+
+            let balance_after = test::get_account_balance::<Env>(accounts.bob).unwrap();
+            assert_eq!(balance_after, balance_before + 5);
+        }
+
+        /// We test that paying exactly the price triggers no refund.
+        #[ink::test]
+        fn manic_mint_exact_payment_does_not_refund() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+            manicminter.set_price(10).unwrap();
+
+            test::set_caller::<Env>(accounts.bob);
+            test::set_value_transferred::<Env>(10);
+            let balance_before = test::get_account_balance::<Env>(accounts.bob).unwrap();
+
+            // The cross-contract mint call that follows the refund check panics off-chain.
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                manicminter.manic_mint(1)
+            }));
+
+            let balance_after = test::get_account_balance::<Env>(accounts.bob).unwrap();
+            assert_eq!(balance_after, balance_before);
+        }
+
+        /// We test that `get_price_for` falls back to the default price until
+        /// the owner sets a discount for that account.
+        #[ink::test]
+        fn get_price_for_falls_back_to_default_price() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+            manicminter.set_price(10).unwrap();
+
+            assert_eq!(manicminter.get_price_for(accounts.bob), 10);
+
+            assert_eq!(manicminter.set_account_price(accounts.bob, 4), Ok(()));
+            assert_eq!(manicminter.get_price_for(accounts.bob), 4);
+
+            // Other accounts are unaffected.
+            assert_eq!(manicminter.get_price_for(accounts.charlie), 10);
+        }
+
+        /// We test that the owner can set and read back the mint selector.
+        #[ink::test]
+        fn set_mint_selector_round_trips() {
+            let mut manicminter = Manicminter::new(token_contract_account());
+            let selector = ink::selector_bytes!("CustomToken::mint");
+
+            assert_eq!(manicminter.set_mint_selector(selector), Ok(()));
+            assert_eq!(manicminter.get_mint_selector(), selector);
+        }
+
+        /// We test that a non-owner cannot change the mint selector.
+        #[ink::test]
+        fn set_mint_selector_by_non_owner_fails() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(
+                manicminter.set_mint_selector([0x0; 4]),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// We test that the owner can set and read back the cross-contract gas limit.
+        #[ink::test]
+        fn set_gas_limit_round_trips() {
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            assert_eq!(manicminter.set_gas_limit(1_000), Ok(()));
+            assert_eq!(manicminter.get_gas_limit(), 1_000);
+        }
+
+        /// We test that a non-owner cannot change the gas limit.
+        #[ink::test]
+        fn set_gas_limit_by_non_owner_fails() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(manicminter.set_gas_limit(1_000), Err(Error::NotOwner));
+        }
+
+        /// We test that the owner can set and read back the supply cap, and
+        /// that it defaults to unset.
+        #[ink::test]
+        fn set_max_supply_round_trips() {
+            let mut manicminter = Manicminter::new(token_contract_account());
+            assert_eq!(manicminter.get_max_supply(), None);
+
+            assert_eq!(manicminter.set_max_supply(Some(100)), Ok(()));
+            assert_eq!(manicminter.get_max_supply(), Some(100));
+
+            assert_eq!(manicminter.set_max_supply(None), Ok(()));
+            assert_eq!(manicminter.get_max_supply(), None);
+        }
+
+        /// We test that a non-owner cannot change the supply cap.
+        #[ink::test]
+        fn set_max_supply_by_non_owner_fails() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(manicminter.set_max_supply(Some(100)), Err(Error::NotOwner));
+        }
+
+        /// We test that the owner can withdraw funds held by the contract.
+        #[ink::test]
+        fn withdraw_by_owner_succeeds() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+            let contract_account = test::callee::<Env>();
+            test::set_account_balance::<Env>(contract_account, 100);
+
+            let balance_before = test::get_account_balance::<Env>(accounts.alice).unwrap();
+            assert_eq!(manicminter.withdraw(40), Ok(()));
+            let balance_after = test::get_account_balance::<Env>(accounts.alice).unwrap();
+            assert_eq!(balance_after, balance_before + 40);
+        }
+
+        /// We test that a non-owner cannot withdraw.
+        #[ink::test]
+        fn withdraw_by_non_owner_fails() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(manicminter.withdraw(1), Err(Error::NotOwner));
         }
 
-        /// We test a simple use case of our contract.
+        /// We test that a proposed owner must accept before ownership moves,
+        /// and that only the proposed account can do so.
         #[ink::test]
-        fn it_works() {
-            let mut manicminter = Manicminter::new(false);
-            assert_eq!(manicminter.get(), false);
-            manicminter.flip();
-            assert_eq!(manicminter.get(), true);
+        fn ownership_transfer_requires_acceptance_by_proposed_owner() {
+            let accounts = test::default_accounts::<Env>();
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            manicminter.transfer_ownership(accounts.bob).unwrap();
+
+            test::set_caller::<Env>(accounts.charlie);
+            assert_eq!(manicminter.accept_ownership(), Err(Error::NotPendingOwner));
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(manicminter.accept_ownership(), Ok(()));
+
+            // The old owner has lost access, and the new owner has gained it.
+            test::set_caller::<Env>(accounts.alice);
+            assert_eq!(manicminter.set_price(5), Err(Error::NotOwner));
+
+            test::set_caller::<Env>(accounts.bob);
+            assert_eq!(manicminter.set_price(5), Ok(()));
+        }
+
+        /// We test that renouncing ownership locks the former owner out of
+        /// owner-gated messages such as `set_price`.
+        #[ink::test]
+        fn renounce_ownership_locks_out_owner_gated_messages() {
+            let mut manicminter = Manicminter::new(token_contract_account());
+
+            assert_eq!(manicminter.renounce_ownership(), Ok(()));
+            assert_eq!(manicminter.set_price(5), Err(Error::NotOwner));
         }
     }
 